@@ -1,13 +1,3 @@
-use std::io::Write;
-use std::path::Path;
-
-use openssl::ssl::{SslConnectorBuilder, SslConnector, SslMethod};
-use openssl::error::ErrorStack;
-use openssl::x509::X509_FILETYPE_PEM;
-use openssl_sys::TLSv1_2_method;
-
-use hyper::net::{HttpsConnector, Fresh};
-use hyper_openssl::OpensslClient;
 use hyper::method::Method;
 use hyper::Client;
 use hyper::client::RequestBuilder;
@@ -15,99 +5,617 @@ use hyper::client::request::Request;
 
 use url::Url;
 
-pub fn ssl_connector<C>(cacert: C, cert: Option<C>, key: Option<C>) -> Result<SslConnector, ErrorStack>
-    where C: AsRef<Path>
-{
-    unsafe {
-        let mut connector = SslConnectorBuilder::new(SslMethod::from_ptr(TLSv1_2_method())).unwrap();
-        {
-            let mut ctx = connector.builder_mut();
-            try!(ctx.set_cipher_list("DHE-RSA-AES128-GCM-SHA256:DHE-RSA-AES256-GCM-SHA384:\
-                                      DHE-RSA-AES128-SHA256:DHE-RSA-AES256-SHA256:\
-                                      DHE-RSA-CAMELLIA128-SHA:DHE-RSA-AES128-SHA:\
-                                      DHE-RSA-CAMELLIA256-SHA:DHE-RSA-AES256-SHA:AES128-GCM-SHA256:\
-                                      AES256-GCM-SHA384:CAMELLIA128-SHA:AES128-SHA:!aNULL:!eNULL:\
-                                      !EXPORT:!DES:!3DES:!RC4:!MD5"));
-            try!(ctx.set_ca_file(cacert.as_ref()));
-            // TODO should validate both key and cert are set when either one is
-            // specified
-            if let Some(cert) = cert {
-                try!(ctx.set_certificate_file(cert.as_ref(), X509_FILETYPE_PEM));
-            };
-            if let Some(key) = key {
-                try!(ctx.set_private_key_file(key.as_ref(), X509_FILETYPE_PEM));
-            };
+header! { (XAuthentication, "X-Authentication") => [String] }
+
+// `openssl_tls` and `rustls_tls` both re-export `CertSource`/`Auth`/
+// `ssl_connector`/`https_connector` into the crate root, so exactly one must
+// be compiled in. `openssl` is the default backend; building with
+// `--features rustls` requires `--no-default-features` too, or this fails
+// loudly instead of colliding on duplicate re-exports.
+#[cfg(all(feature = "openssl", feature = "rustls"))]
+compile_error!("features `openssl` and `rustls` are mutually exclusive TLS backends; build with `--no-default-features --features rustls` to select rustls");
+
+#[cfg(feature = "openssl")]
+pub use self::openssl_tls::{CertSource, ssl_connector, https_connector, Auth};
+
+#[cfg(feature = "openssl")]
+mod openssl_tls {
+    use std::fmt;
+    use std::io;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+
+    // SslVersion/set_min_proto_version/set_max_proto_version/set_ciphersuites
+    // only exist from openssl-rs 0.10 onward, so this module is pinned to
+    // 0.10's API throughout: `SslConnector::builder` (not the removed
+    // `SslConnectorBuilder::new`), `SslFiletype` (not the 0.9-only
+    // `X509_FILETYPE_PEM` constant), and no separate `builder_mut()` step
+    // since `SslConnectorBuilder` derefs straight to `SslContextBuilder`.
+    use openssl::ssl::{SslConnectorBuilder, SslConnector, SslMethod, SslFiletype, SslVerifyMode, SslVersion};
+    use openssl::error::ErrorStack;
+    use openssl::x509::X509;
+    use openssl::pkey::PKey;
+
+    use rustls_native_certs;
+
+    use hyper::net::{HttpsConnector, Fresh};
+    use hyper_openssl::OpensslClient;
+    use hyper::method::Method;
+    use hyper::Client;
+    use hyper::client::RequestBuilder;
+    use hyper::client::request::Request;
+
+    use url::Url;
+
+    use super::XAuthentication;
+
+    /// Where to load a CA bundle, client certificate, or private key from. A
+    /// `Path` is read from disk exactly as before; `Pem`/`Der` let callers hand
+    /// over material they already hold in memory (e.g. pulled from a secrets
+    /// manager) without writing it out to a temp file first.
+    #[derive(Clone)]
+    pub enum CertSource {
+        Path(PathBuf),
+        Pem(Vec<u8>),
+        Der(Vec<u8>),
+    }
+
+    impl<P: AsRef<Path>> From<P> for CertSource {
+        fn from(path: P) -> CertSource {
+            CertSource::Path(path.as_ref().to_path_buf())
+        }
+    }
+
+    /// Error building a connector: either an OpenSSL failure (bad cert/key
+    /// material, cipher policy rejected, ...) or an I/O failure reading a
+    /// `CertSource::Path` or the platform's native root store.
+    #[derive(Debug)]
+    pub enum TlsError {
+        Ssl(ErrorStack),
+        Io(io::Error),
+    }
+
+    impl From<ErrorStack> for TlsError {
+        fn from(e: ErrorStack) -> TlsError {
+            TlsError::Ssl(e)
+        }
+    }
+
+    impl From<io::Error> for TlsError {
+        fn from(e: io::Error) -> TlsError {
+            TlsError::Io(e)
+        }
+    }
+
+    impl fmt::Display for TlsError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                &TlsError::Ssl(ref e) => write!(f, "{}", e),
+                &TlsError::Io(ref e) => write!(f, "{}", e),
+            }
+        }
+    }
+
+    const DEFAULT_CIPHER_LIST: &'static str =
+        "DHE-RSA-AES128-GCM-SHA256:DHE-RSA-AES256-GCM-SHA384:\
+         DHE-RSA-AES128-SHA256:DHE-RSA-AES256-SHA256:\
+         DHE-RSA-CAMELLIA128-SHA:DHE-RSA-AES128-SHA:\
+         DHE-RSA-CAMELLIA256-SHA:DHE-RSA-AES256-SHA:AES128-GCM-SHA256:\
+         AES256-GCM-SHA384:CAMELLIA128-SHA:AES128-SHA:!aNULL:!eNULL:\
+         !EXPORT:!DES:!3DES:!RC4:!MD5";
+
+    /// Controls which protocol versions and ciphers a connector will
+    /// negotiate. The default (`TlsPolicy::default()`) allows TLS 1.2-1.3
+    /// with the crate's standard cipher list; set `min_version` to pin a
+    /// floor, or `cipher_list`/`cipher_suites` to supply your own policy.
+    #[derive(Clone, Default)]
+    pub struct TlsPolicy {
+        pub min_version: Option<SslVersion>,
+        pub cipher_list: Option<String>,
+        pub cipher_suites: Option<String>,
+    }
+
+    /// Builds the verification store for a connection. `Some(cacert)` pins
+    /// trust to that CA bundle exactly as before; `None` falls back to the
+    /// platform's native trust store so callers can skip shipping a CA file
+    /// when they're happy trusting whatever the OS already trusts.
+    /// `danger_accept_invalid_certs` disables certificate verification
+    /// entirely (no trust-chain or hostname checks). It exists for talking to
+    /// self-signed/mismatched servers during local development; it must never
+    /// be set from production configuration.
+    pub fn ssl_connector(cacert: Option<CertSource>, cert: Option<CertSource>, key: Option<CertSource>, danger_accept_invalid_certs: bool, tls_policy: TlsPolicy) -> Result<SslConnector, TlsError>
+    {
+        let mut connector = try!(new_builder(&tls_policy));
+        try!(set_trust(&mut connector, cacert));
+        // TODO should validate both key and cert are set when either one is
+        // specified
+        if let Some(cert) = cert {
+            match cert {
+                CertSource::Path(path) => try!(connector.set_certificate_file(path, SslFiletype::PEM)),
+                CertSource::Pem(bytes) => try!(connector.set_certificate(&try!(X509::from_pem(&bytes)))),
+                CertSource::Der(bytes) => try!(connector.set_certificate(&try!(X509::from_der(&bytes)))),
+            }
+        };
+        if let Some(key) = key {
+            match key {
+                CertSource::Path(path) => try!(connector.set_private_key_file(path, SslFiletype::PEM)),
+                CertSource::Pem(bytes) => try!(connector.set_private_key(&try!(PKey::private_key_from_pem(&bytes)))),
+                CertSource::Der(bytes) => try!(connector.set_private_key(&try!(PKey::private_key_from_der(&bytes)))),
+            }
+        };
+        if danger_accept_invalid_certs {
+            connector.set_verify(SslVerifyMode::NONE);
         }
         Ok(connector.build())
     }
-}
 
-pub fn https_connector<C>(cacert: C, cert: Option<C>, key: Option<C>) -> HttpsConnector<OpensslClient>
-    where C: AsRef<Path>
-{
-    let connector = match ssl_connector(cacert, cert, key) {
-        Ok(connector) => connector,
-        Err(e) => pretty_panic!("Error opening certificate files: {}", e),
-    };
-    HttpsConnector::new(OpensslClient::from(connector))
-}
+    /// Like `ssl_connector`, but installs a client identity from a single
+    /// password-protected PKCS#12 bundle instead of separate cert/key files,
+    /// avoiding the split-file footgun noted above.
+    pub fn ssl_connector_p12(cacert: Option<CertSource>, p12: CertSource, passphrase: &str, danger_accept_invalid_certs: bool, tls_policy: TlsPolicy) -> Result<SslConnector, TlsError>
+    {
+        use openssl::pkcs12::Pkcs12;
 
-header! { (XAuthentication, "X-Authentication") => [String] }
+        let p12_bytes = match p12 {
+            CertSource::Path(path) => try!(::std::fs::read(path)),
+            CertSource::Pem(bytes) | CertSource::Der(bytes) => bytes,
+        };
+        let pkcs12 = try!(Pkcs12::from_der(&p12_bytes));
+        let identity = try!(pkcs12.parse(passphrase));
+
+        let mut connector = try!(new_builder(&tls_policy));
+        try!(set_trust(&mut connector, cacert));
+        try!(connector.set_certificate(&identity.cert));
+        try!(connector.set_private_key(&identity.pkey));
+        // `ParsedPkcs12.chain` is `Option<Stack<X509>>` from openssl-rs 0.10
+        // onward (0.9's is a bare, non-optional `Stack<X509>`); this module
+        // is pinned to 0.10, see the note on the imports above.
+        if let Some(chain) = identity.chain {
+            for cert in chain {
+                try!(connector.add_extra_chain_cert(cert));
+            }
+        }
+        if danger_accept_invalid_certs {
+            connector.set_verify(SslVerifyMode::NONE);
+        }
+        Ok(connector.build())
+    }
+
+    /// Allows TLS 1.2-1.3 by default (`SslMethod::tls()` negotiates the
+    /// highest version both sides support); `tls_policy.min_version`, when
+    /// set, replaces the default minimum of TLS 1.2, and `cipher_list`/
+    /// `cipher_suites` override the standard cipher policy.
+    fn new_builder(tls_policy: &TlsPolicy) -> Result<SslConnectorBuilder, ErrorStack> {
+        let mut connector = try!(SslConnector::builder(SslMethod::tls()));
+        try!(connector.set_min_proto_version(Some(tls_policy.min_version.unwrap_or(SslVersion::TLS1_2))));
+        try!(connector.set_max_proto_version(Some(SslVersion::TLS1_3)));
+        let cipher_list = tls_policy.cipher_list.as_ref().map(|s| s.as_str()).unwrap_or(DEFAULT_CIPHER_LIST);
+        try!(connector.set_cipher_list(cipher_list));
+        if let Some(ref cipher_suites) = tls_policy.cipher_suites {
+            try!(connector.set_ciphersuites(cipher_suites));
+        }
+        Ok(connector)
+    }
+
+    fn set_trust(ctx: &mut ::openssl::ssl::SslContextBuilder, cacert: Option<CertSource>) -> Result<(), TlsError> {
+        match cacert {
+            Some(CertSource::Path(path)) => try!(ctx.set_ca_file(path)),
+            Some(CertSource::Pem(bytes)) => {
+                let ca = try!(X509::from_pem(&bytes));
+                try!(ctx.cert_store_mut().add_cert(ca));
+            }
+            Some(CertSource::Der(bytes)) => {
+                let ca = try!(X509::from_der(&bytes));
+                try!(ctx.cert_store_mut().add_cert(ca));
+            }
+            None => {
+                // Load errors (e.g. the platform trust store can't be read)
+                // are returned to the caller instead of silently trusting
+                // nothing or panicking. Pinned to rustls-native-certs 0.6.x,
+                // whose `load_native_certs` returns `Result<Vec<Certificate>,
+                // io::Error>` (older 0.x releases return a
+                // `(Option<RootCertStore>, Error)` tuple on error instead).
+                let native_certs = try!(rustls_native_certs::load_native_certs());
+                let mut store = ctx.cert_store_mut();
+                let mut unparseable = 0usize;
+                for cert in &native_certs {
+                    match X509::from_der(cert.as_ref()) {
+                        Ok(cert) => try!(store.add_cert(cert)),
+                        Err(_) => unparseable += 1,
+                    }
+                }
+                if unparseable > 0 {
+                    eprintln!("warning: failed to parse {} of {} native root certificates",
+                              unparseable, native_certs.len());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn https_connector(cacert: Option<CertSource>, cert: Option<CertSource>, key: Option<CertSource>, danger_accept_invalid_certs: bool, tls_policy: TlsPolicy) -> HttpsConnector<OpensslClient>
+    {
+        let connector = match ssl_connector(cacert, cert, key, danger_accept_invalid_certs, tls_policy) {
+            Ok(connector) => connector,
+            Err(e) => pretty_panic!("Error opening certificate files: {}", e),
+        };
+        HttpsConnector::new(OpensslClient::from(connector))
+    }
+
+    pub fn https_connector_p12(cacert: Option<CertSource>, p12: CertSource, passphrase: &str, danger_accept_invalid_certs: bool, tls_policy: TlsPolicy) -> HttpsConnector<OpensslClient>
+    {
+        let connector = match ssl_connector_p12(cacert, p12, passphrase, danger_accept_invalid_certs, tls_policy) {
+            Ok(connector) => connector,
+            Err(e) => pretty_panic!("Error opening certificate files: {}", e),
+        };
+        HttpsConnector::new(OpensslClient::from(connector))
+    }
+
+    pub enum Auth {
+        CertAuth {
+            connector: Arc<HttpsConnector<OpensslClient>>,
+        },
+        NoAuth,
+        TokenAuth {
+            connector: Arc<HttpsConnector<OpensslClient>>,
+            token: String,
+        },
+    }
+
+    impl Auth {
+        /// Builds the `SslConnector`/`HttpsConnector` once up front so that
+        /// `client`/`request` can hand out requests against the same connector
+        /// instead of re-reading the CA/cert/key and reconstructing OpenSSL
+        /// state on every call. `cacert: None` trusts the platform's native
+        /// root certificates instead of requiring an explicit CA bundle.
+        /// `danger_accept_invalid_certs` disables TLS verification entirely and
+        /// must only ever be turned on for local development, never production.
+        pub fn cert_auth(cacert: Option<CertSource>, cert: CertSource, key: CertSource, danger_accept_invalid_certs: bool, tls_policy: TlsPolicy) -> Result<Auth, TlsError> {
+            let connector = try!(ssl_connector(cacert, Some(cert), Some(key), danger_accept_invalid_certs, tls_policy));
+            Ok(Auth::CertAuth {
+                connector: Arc::new(HttpsConnector::new(OpensslClient::from(connector))),
+            })
+        }
+
+        /// Like `cert_auth`, but installs a client identity from a single
+        /// password-protected PKCS#12 bundle instead of separate cert/key files.
+        pub fn cert_auth_p12(cacert: Option<CertSource>, p12: CertSource, passphrase: &str, danger_accept_invalid_certs: bool, tls_policy: TlsPolicy) -> Result<Auth, TlsError> {
+            let connector = try!(ssl_connector_p12(cacert, p12, passphrase, danger_accept_invalid_certs, tls_policy));
+            Ok(Auth::CertAuth {
+                connector: Arc::new(HttpsConnector::new(OpensslClient::from(connector))),
+            })
+        }
+
+        pub fn token_auth(cacert: Option<CertSource>, token: String, danger_accept_invalid_certs: bool, tls_policy: TlsPolicy) -> Result<Auth, TlsError> {
+            let connector = try!(ssl_connector(cacert, None, None, danger_accept_invalid_certs, tls_policy));
+            Ok(Auth::TokenAuth {
+                connector: Arc::new(HttpsConnector::new(OpensslClient::from(connector))),
+                token: token,
+            })
+        }
 
-pub enum Auth {
-    CertAuth {
-        cacert: String,
-        cert: String,
-        key: String,
-    },
-    NoAuth,
-    TokenAuth {
-        cacert: String,
-        token: String,
-    },
+        pub fn client(&self) -> Client {
+            match self {
+                &Auth::CertAuth { ref connector } => Client::with_connector((**connector).clone()),
+                &Auth::TokenAuth { ref connector, .. } => Client::with_connector((**connector).clone()),
+                &Auth::NoAuth => Client::new(),
+            }
+        }
+
+        pub fn request(&self, method: Method, url: Url) -> Request<Fresh> {
+            match self {
+                &Auth::CertAuth { ref connector } => {
+                    Request::<Fresh>::with_connector(method, url, &**connector).unwrap()
+                }
+                &Auth::TokenAuth { ref connector, ref token } => {
+                    let mut req = Request::<Fresh>::with_connector(method, url, &**connector).unwrap();
+                    req.headers_mut().set(XAuthentication(token.clone()));
+                    req
+                }
+                &Auth::NoAuth => Request::<Fresh>::new(method, url).unwrap(),
+            }
+        }
+
+        pub fn auth_header<'a>(&self, request_builder: RequestBuilder<'a>) -> RequestBuilder<'a> {
+            match self {
+                &Auth::TokenAuth { ref token, .. } => {
+                    request_builder.header(XAuthentication(token.clone()))
+                }
+                _ => request_builder,
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{ssl_connector, CertSource, TlsPolicy};
+
+        const VALID_CERT_PEM: &'static [u8] = include_bytes!("../testdata/test_cert.pem");
+
+        #[test]
+        fn accepts_a_valid_pem_ca_cert() {
+            let cacert = CertSource::Pem(VALID_CERT_PEM.to_vec());
+            let result = ssl_connector(Some(cacert), None, None, false, TlsPolicy::default());
+            assert!(result.is_ok());
+        }
+
+        #[test]
+        fn rejects_garbage_pem_ca_cert() {
+            let cacert = CertSource::Pem(b"not a certificate".to_vec());
+            let result = ssl_connector(Some(cacert), None, None, false, TlsPolicy::default());
+            assert!(result.is_err());
+        }
+    }
 }
 
-impl Auth {
-    pub fn client(&self) -> Client {
-        match self {
-            &Auth::CertAuth { ref cacert, ref cert, ref key } => {
-                let conn = https_connector(Path::new(cacert),
-                                         Some(Path::new(cert)),
-                                         Some(Path::new(key)));
-                Client::with_connector(conn)
+/// Pure-Rust TLS backend built on `rustls` + `tokio-rustls`, selected with
+/// `--features rustls` instead of the default `openssl` backend. Exposes the
+/// same `CertSource`/`ssl_connector`/`https_connector`/`Auth` surface as the
+/// openssl backend, including the native-root fallback, `danger_accept_invalid_certs`,
+/// and a minimal protocol-version policy, so most callers can switch backends
+/// without touching call sites. One gap remains: there is no PKCS#12 support
+/// here (no `ssl_connector_p12`/`https_connector_p12`/`Auth::cert_auth_p12`) —
+/// rustls has no PKCS#12 parser, so a p12 bundle must be split into PEM
+/// cert/key material (e.g. with `openssl pkcs12`) before use with this
+/// backend.
+#[cfg(feature = "rustls")]
+pub use self::rustls_tls::{CertSource, TlsPolicy, MinVersion, ssl_connector, https_connector, Auth};
+
+#[cfg(feature = "rustls")]
+mod rustls_tls {
+    use std::fs::File;
+    use std::io::{self, BufReader};
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+
+    use rustls::{self, ClientConfig, RootCertStore, ProtocolVersion};
+    use rustls::internal::pemfile::{certs, rsa_private_keys, pkcs8_private_keys};
+
+    use rustls_native_certs;
+    use webpki;
+
+    use hyper_rustls::HttpsConnector;
+    use hyper::method::Method;
+    use hyper::Client;
+    use hyper::client::RequestBuilder;
+    use hyper::client::request::Request;
+    use hyper::net::Fresh;
+
+    use url::Url;
+
+    use super::XAuthentication;
+
+    /// Where to load a CA bundle, client certificate, or private key from. A
+    /// `Path` is read from disk exactly as before; `Pem`/`Der` let callers hand
+    /// over material they already hold in memory without writing it to a temp
+    /// file first.
+    #[derive(Clone)]
+    pub enum CertSource {
+        Path(PathBuf),
+        Pem(Vec<u8>),
+        Der(Vec<u8>),
+    }
+
+    impl<P: AsRef<Path>> From<P> for CertSource {
+        fn from(path: P) -> CertSource {
+            CertSource::Path(path.as_ref().to_path_buf())
+        }
+    }
+
+    impl CertSource {
+        fn into_pem_bytes(self) -> io::Result<Vec<u8>> {
+            match self {
+                CertSource::Path(path) => {
+                    use std::io::Read;
+                    let mut buf = Vec::new();
+                    try!(File::open(path)).read_to_end(&mut buf).map(|_| buf)
+                }
+                CertSource::Pem(bytes) => Ok(bytes),
+                CertSource::Der(bytes) => Ok(bytes),
             }
-            &Auth::TokenAuth { ref cacert, .. } => {
-                let conn = https_connector(Path::new(cacert), None, None);
-                Client::with_connector(conn)
+        }
+    }
+
+    /// Floor for the protocol versions a connector will offer. rustls only
+    /// negotiates down to `Tls12` today; `Tls13` pins the connection to TLS
+    /// 1.3 only. Unlike the openssl backend's `TlsPolicy`, cipher suite
+    /// selection isn't exposed since rustls ships a single vetted suite list.
+    #[derive(Clone, Copy)]
+    pub enum MinVersion {
+        Tls12,
+        Tls13,
+    }
+
+    #[derive(Clone)]
+    pub struct TlsPolicy {
+        pub min_version: MinVersion,
+    }
+
+    impl Default for TlsPolicy {
+        fn default() -> TlsPolicy {
+            TlsPolicy { min_version: MinVersion::Tls12 }
+        }
+    }
+
+    struct NoCertificateVerification;
+
+    impl rustls::ServerCertVerifier for NoCertificateVerification {
+        fn verify_server_cert(&self,
+                               _roots: &RootCertStore,
+                               _presented_certs: &[rustls::Certificate],
+                               _dns_name: webpki::DNSNameRef,
+                               _ocsp_response: &[u8])
+                               -> Result<rustls::ServerCertVerified, rustls::TLSError> {
+            Ok(rustls::ServerCertVerified::assertion())
+        }
+    }
+
+    /// Builds the verification store. `Some(cacert)` pins trust to that CA
+    /// bundle exactly as before; `None` falls back to the platform's native
+    /// trust store, mirroring the openssl backend, so callers can skip
+    /// shipping a CA file when they're happy trusting whatever the OS already
+    /// trusts. Certificates that fail to load into the store are counted and
+    /// reported rather than silently dropped.
+    fn set_trust(cacert: Option<CertSource>) -> io::Result<RootCertStore> {
+        let mut roots = RootCertStore::empty();
+        match cacert {
+            Some(CertSource::Der(bytes)) => {
+                try!(roots.add(&rustls::Certificate(bytes))
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid CA certificate")));
+            }
+            Some(cacert) => {
+                let ca_pem = try!(cacert.into_pem_bytes());
+                let (valid, invalid) = try!(roots.add_pem_file(&mut BufReader::new(&ca_pem[..]))
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid CA certificate")));
+                if invalid > 0 {
+                    eprintln!("warning: failed to add {} of {} CA certificates",
+                              invalid, valid + invalid);
+                }
+            }
+            None => {
+                // Pinned to rustls-native-certs 0.6.x: `load_native_certs`
+                // returns `Result<Vec<Certificate>, io::Error>`, not the
+                // `(Option<_>, Error)` tuple older 0.x releases used.
+                let native_certs = try!(rustls_native_certs::load_native_certs());
+                let mut unparseable = 0usize;
+                for cert in &native_certs {
+                    if roots.add(&rustls::Certificate(cert.0.clone())).is_err() {
+                        unparseable += 1;
+                    }
+                }
+                if unparseable > 0 {
+                    eprintln!("warning: failed to add {} of {} native root certificates",
+                              unparseable, native_certs.len());
+                }
             }
-            &Auth::NoAuth => Client::new(),
         }
+        Ok(roots)
     }
 
-    pub fn request(&self, method: Method, url: Url) -> Request<Fresh> {
-        match self {
-            &Auth::CertAuth { ref cacert, ref cert, ref key } => {
-                let conn = https_connector(Path::new(cacert),
-                                         Some(Path::new(cert)),
-                                         Some(Path::new(key)));
-                Request::<Fresh>::with_connector(method, url, &conn).unwrap()
+    /// `danger_accept_invalid_certs` disables certificate verification
+    /// entirely (no trust-chain or hostname checks). It exists for talking to
+    /// self-signed/mismatched servers during local development; it must never
+    /// be set from production configuration.
+    pub fn ssl_connector(cacert: Option<CertSource>, cert: Option<CertSource>, key: Option<CertSource>, danger_accept_invalid_certs: bool, tls_policy: TlsPolicy) -> io::Result<ClientConfig> {
+        let mut config = ClientConfig::new();
+        config.root_store = try!(set_trust(cacert));
+        config.versions = match tls_policy.min_version {
+            MinVersion::Tls12 => vec![ProtocolVersion::TLSv1_3, ProtocolVersion::TLSv1_2],
+            MinVersion::Tls13 => vec![ProtocolVersion::TLSv1_3],
+        };
+
+        if let (Some(cert), Some(key)) = (cert, key) {
+            let cert_chain = match cert {
+                CertSource::Der(bytes) => vec![rustls::Certificate(bytes)],
+                cert => {
+                    let cert_pem = try!(cert.into_pem_bytes());
+                    try!(certs(&mut BufReader::new(&cert_pem[..]))
+                        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid client certificate")))
+                }
+            };
+            let der_key = match key {
+                CertSource::Der(ref bytes) => Some(rustls::PrivateKey(bytes.clone())),
+                _ => None,
+            };
+            let key_pem = try!(key.into_pem_bytes());
+            // `rsa_private_keys` only picks up PKCS1 (`BEGIN RSA PRIVATE
+            // KEY`) sections and returns `Ok(vec![])` - not an error - for a
+            // PKCS8 key (`BEGIN PRIVATE KEY`, the only form EC keys use and
+            // the default output of most modern tooling). Fall back to
+            // `pkcs8_private_keys` before giving up.
+            let mut keys = match der_key {
+                Some(der_key) => vec![der_key],
+                None => try!(rsa_private_keys(&mut BufReader::new(&key_pem[..]))
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid private key"))),
+            };
+            if keys.is_empty() {
+                keys = try!(pkcs8_private_keys(&mut BufReader::new(&key_pem[..]))
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid private key")));
             }
-            &Auth::TokenAuth { ref cacert, ref token, .. } => {
-                let conn = https_connector(Path::new(cacert), None, None);
-                let mut req = Request::<Fresh>::with_connector(method, url, &conn).unwrap();
-                req.headers_mut().set(XAuthentication(token.clone()));
-                req
+            if keys.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                           "no PKCS1 or PKCS8 private key found in PEM"));
             }
-            &Auth::NoAuth => Request::<Fresh>::new(method, url).unwrap(),
+            let key = keys.remove(0);
+            try!(config.set_single_client_cert(cert_chain, key)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)));
+        }
+
+        if danger_accept_invalid_certs {
+            config.dangerous().set_certificate_verifier(Arc::new(NoCertificateVerification));
         }
+
+        Ok(config)
+    }
+
+    pub fn https_connector(cacert: Option<CertSource>, cert: Option<CertSource>, key: Option<CertSource>, danger_accept_invalid_certs: bool, tls_policy: TlsPolicy) -> HttpsConnector
+    {
+        let config = match ssl_connector(cacert, cert, key, danger_accept_invalid_certs, tls_policy) {
+            Ok(config) => config,
+            Err(e) => pretty_panic!("Error opening certificate files: {}", e),
+        };
+        HttpsConnector::new(Arc::new(config))
     }
 
-    pub fn auth_header<'a>(&self, request_builder: RequestBuilder<'a>) -> RequestBuilder<'a> {
-        match self {
-            &Auth::TokenAuth { ref token, .. } => {
-                request_builder.header(XAuthentication(token.clone()))
+    pub enum Auth {
+        CertAuth {
+            connector: Arc<HttpsConnector>,
+        },
+        NoAuth,
+        TokenAuth {
+            connector: Arc<HttpsConnector>,
+            token: String,
+        },
+    }
+
+    impl Auth {
+        /// `cacert: None` trusts the platform's native root certificates
+        /// instead of requiring an explicit CA bundle.
+        /// `danger_accept_invalid_certs` disables TLS verification entirely
+        /// and must only ever be turned on for local development, never
+        /// production.
+        pub fn cert_auth(cacert: Option<CertSource>, cert: CertSource, key: CertSource, danger_accept_invalid_certs: bool, tls_policy: TlsPolicy) -> io::Result<Auth> {
+            let config = try!(ssl_connector(cacert, Some(cert), Some(key), danger_accept_invalid_certs, tls_policy));
+            Ok(Auth::CertAuth { connector: Arc::new(HttpsConnector::new(Arc::new(config))) })
+        }
+
+        pub fn token_auth(cacert: Option<CertSource>, token: String, danger_accept_invalid_certs: bool, tls_policy: TlsPolicy) -> io::Result<Auth> {
+            let config = try!(ssl_connector(cacert, None, None, danger_accept_invalid_certs, tls_policy));
+            Ok(Auth::TokenAuth {
+                connector: Arc::new(HttpsConnector::new(Arc::new(config))),
+                token: token,
+            })
+        }
+
+        pub fn client(&self) -> Client {
+            match self {
+                &Auth::CertAuth { ref connector } => Client::with_connector((**connector).clone()),
+                &Auth::TokenAuth { ref connector, .. } => Client::with_connector((**connector).clone()),
+                &Auth::NoAuth => Client::new(),
+            }
+        }
+
+        pub fn request(&self, method: Method, url: Url) -> Request<Fresh> {
+            match self {
+                &Auth::CertAuth { ref connector } => {
+                    Request::<Fresh>::with_connector(method, url, &**connector).unwrap()
+                }
+                &Auth::TokenAuth { ref connector, ref token } => {
+                    let mut req = Request::<Fresh>::with_connector(method, url, &**connector).unwrap();
+                    req.headers_mut().set(XAuthentication(token.clone()));
+                    req
+                }
+                &Auth::NoAuth => Request::<Fresh>::new(method, url).unwrap(),
+            }
+        }
+
+        pub fn auth_header<'a>(&self, request_builder: RequestBuilder<'a>) -> RequestBuilder<'a> {
+            match self {
+                &Auth::TokenAuth { ref token, .. } => {
+                    request_builder.header(XAuthentication(token.clone()))
+                }
+                _ => request_builder,
             }
-            _ => request_builder,
         }
     }
 }